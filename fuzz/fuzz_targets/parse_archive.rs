@@ -0,0 +1,31 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// `Archive::new`, iteration, and `copy_entry_into` must be total over arbitrary bytes: malformed
+// or truncated input should only ever surface as an `Err`, never a panic.
+fuzz_target!(|data: &[u8]| {
+    let Ok(archive) = oaf::Archive::new(data) else {
+        return;
+    };
+
+    let mut block_scratch = vec![0u8; archive.block_size().max(1) as usize];
+
+    for entry in archive.iter() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+
+        // Cap the scratch allocation so a bogus, huge `data_len` can't OOM the fuzzer; the point
+        // here is panic-freedom, not exhaustively covering every declared length.
+        let Ok(len) = usize::try_from(entry.len()) else {
+            continue;
+        };
+        if len > 16 * 1024 * 1024 {
+            continue;
+        }
+
+        let mut out = vec![0u8; len];
+        let _ = archive.copy_entry_into(&entry, &mut out, &mut block_scratch);
+    }
+});
@@ -1,11 +1,25 @@
-use crate::{ArchiveHeader, ArchiveTableEntry, Signature};
+use crate::chunking;
+use crate::compress::{compress_block, Codec, BLOCK_SIZE};
+use crate::hash::{Digest256, SimpleDigest};
+use crate::{
+    crc32, ArchiveHeader, ArchiveTableEntry, BlockEntry, ChunkEntry, EntryMetadata, Error,
+    PartHeader, Result, Signature,
+};
+use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
 
 pub struct ArchiveBuilder {
     names: Vec<u8>,
     extra_data: Vec<u8>,
-    data: Vec<u8>,
+    /// The deduplicated pool of unique chunk bytes, in order of first appearance.
+    chunk_pool: Vec<u8>,
+    chunk_table: Vec<ChunkEntry>,
+    /// Maps a chunk's content hash to its index in `chunk_table`, for dedup.
+    chunk_hashes: BTreeMap<[u8; 32], u32>,
+    /// Flat, per-entry-concatenated chunk references into `chunk_table`.
+    chunk_index: Vec<u32>,
     table_entries: Vec<ArchiveTableEntry>,
+    codec: Codec,
 }
 
 impl ArchiveBuilder {
@@ -13,12 +27,51 @@ impl ArchiveBuilder {
         ArchiveBuilder {
             names: Vec::new(),
             extra_data: Vec::new(),
-            data: Vec::new(),
+            chunk_pool: Vec::new(),
+            chunk_table: Vec::new(),
+            chunk_hashes: BTreeMap::new(),
+            chunk_index: Vec::new(),
             table_entries: Vec::new(),
+            codec: Codec::Store,
         }
     }
 
+    /// Selects the codec used to compress the data blocks written by [`Self::finish`].
+    pub const fn with_codec(mut self, codec: Codec) -> Self {
+        self.codec = codec;
+        self
+    }
+
     pub fn push_entry(&mut self, signature: Signature, name: &str, extra_data: &[u8], data: &[u8]) {
+        self.push_entry_inner(signature, name, extra_data, data);
+    }
+
+    /// Like [`Self::push_entry`], but also attaches [`EntryMetadata`] (POSIX mode/uid/gid/mtime
+    /// and, for device entries, major/minor numbers) to the entry, as the fixed-size prefix of
+    /// its `extra_data`. For `Signature::Symlink` entries, `symlink_target` is appended after the
+    /// metadata as the link's target path; it is ignored for other signatures.
+    pub fn push_metadata_entry(
+        &mut self,
+        signature: Signature,
+        name: &str,
+        metadata: EntryMetadata,
+        symlink_target: Option<&str>,
+        data: &[u8],
+    ) {
+        let mut extra_data = Vec::with_capacity(
+            core::mem::size_of::<EntryMetadata>() + symlink_target.map_or(0, str::len),
+        );
+        extra_data.extend_from_slice(bytemuck::bytes_of(&metadata));
+        if signature == Signature::Symlink {
+            if let Some(target) = symlink_target {
+                extra_data.extend_from_slice(target.as_bytes());
+            }
+        }
+
+        self.push_entry_inner(signature, name, &extra_data, data);
+    }
+
+    fn push_entry_inner(&mut self, signature: Signature, name: &str, extra_data: &[u8], data: &[u8]) {
         let name_offset = self.names.len().try_into().unwrap();
         let name_len = name.len().try_into().unwrap();
         self.names.extend_from_slice(name.as_bytes());
@@ -27,9 +80,17 @@ impl ArchiveBuilder {
         let extra_data_len = extra_data.len().try_into().unwrap();
         self.extra_data.extend_from_slice(extra_data);
 
-        let data_offset = self.data.len().try_into().unwrap();
-        let data_len = data.len().try_into().unwrap();
-        self.data.extend_from_slice(data);
+        let data_crc32 = crc32::crc32(data);
+
+        let chunk_ref_offset: u64 = self.chunk_index.len().try_into().unwrap();
+        let mut remaining = data;
+        while !remaining.is_empty() {
+            let chunk = chunking::next_chunk(remaining);
+            let chunk_index = self.intern_chunk(chunk);
+            self.chunk_index.push(chunk_index);
+            remaining = &remaining[chunk.len()..];
+        }
+        let chunk_ref_count = u64::try_from(self.chunk_index.len()).unwrap() - chunk_ref_offset;
 
         self.table_entries.push(ArchiveTableEntry::new(
             signature,
@@ -37,16 +98,169 @@ impl ArchiveBuilder {
             name_len,
             extra_data_offset,
             extra_data_len,
-            data_offset,
-            data_len,
+            chunk_ref_offset,
+            chunk_ref_count,
+            data_crc32,
         ));
     }
 
-    pub fn finish(self) -> Vec<u8> {
+    /// Interns `chunk` into the global chunk pool, reusing an existing entry if an identical
+    /// chunk has already been pushed. Returns the chunk's index into the chunk table.
+    fn intern_chunk(&mut self, chunk: &[u8]) -> u32 {
+        let mut digest = SimpleDigest::default();
+        digest.update(chunk);
+        let chunk_hash = digest.finalize();
+
+        if let Some(&index) = self.chunk_hashes.get(&chunk_hash) {
+            return index;
+        }
+
+        let pool_offset = self.chunk_pool.len().try_into().unwrap();
+        self.chunk_pool.extend_from_slice(chunk);
+
+        let index = u32::try_from(self.chunk_table.len()).unwrap();
+        self.chunk_table
+            .push(ChunkEntry::new(pool_offset, chunk.len().try_into().unwrap()));
+        self.chunk_hashes.insert(chunk_hash, index);
+
+        index
+    }
+
+    /// Returns the (not-yet-finalized) name of the `index`-th pushed entry.
+    fn entry_name(&self, index: u32) -> &str {
+        let entry = &self.table_entries[index as usize];
+        let name_offset = usize::try_from(entry.name_offset()).unwrap();
+        let name_len = usize::try_from(entry.name_len()).unwrap();
+        core::str::from_utf8(&self.names[name_offset..(name_offset + name_len)])
+            .expect("entry name was pushed as valid UTF-8")
+    }
+
+    /// Assembles the complete archive. Returns `Error::DuplicateName` if two entries were pushed
+    /// with the same name, since that would make [`crate::Archive::get`]'s lookup ambiguous.
+    pub fn finish(self) -> Result<Vec<u8>> {
+        self.finish_with::<SimpleDigest>()
+    }
+
+    /// Like [`Self::finish`], but hashes the archive's content with a caller-supplied digest
+    /// (e.g. a `sha2::Sha256` wrapper) instead of the built-in [`SimpleDigest`].
+    pub fn finish_with<H: Digest256>(self) -> Result<Vec<u8>> {
+        Ok(self.build::<H>()?.0)
+    }
+
+    /// Like [`Self::finish`], but instead of one contiguous archive, emits it as a sequence of
+    /// parts, each a [`crate::PartHeader`] followed by a slice of the archive. Parts are cut only
+    /// between the metadata prefix (header through `extra_data`) and the compressed data blocks,
+    /// or between two blocks; neither unit is ever split. A part is no larger than
+    /// `max_part_size` unless it holds a single unit that alone exceeds it. Cuts do not follow
+    /// entry boundaries, so an entry's data may span several parts. Reassemble with
+    /// [`crate::Archive::from_parts`].
+    pub fn finish_split(self, max_part_size: u64) -> Result<Vec<Vec<u8>>> {
+        self.finish_split_with::<SimpleDigest>(max_part_size)
+    }
+
+    /// Like [`Self::finish_split`], but hashes the archive's content with a caller-supplied digest
+    /// instead of the built-in [`SimpleDigest`].
+    pub fn finish_split_with<H: Digest256>(self, max_part_size: u64) -> Result<Vec<Vec<u8>>> {
+        let (archive_bytes, data_offset, block_entries) = self.build::<H>()?;
+
+        let header_size = core::mem::size_of::<ArchiveHeader>();
+        let content_hash_offset = header_size - core::mem::size_of::<[u8; 32]>();
+        let mut archive_id = [0u8; 16];
+        archive_id.copy_from_slice(&archive_bytes[content_hash_offset..(content_hash_offset + 16)]);
+
+        // Atomic units that must never be split across parts: the metadata prefix (header through
+        // `extra_data`), followed by one unit per compressed data block.
+        let mut units = Vec::with_capacity(1 + block_entries.len());
+        units.push((0usize, data_offset));
+        let mut block_start = data_offset;
+        for block in &block_entries {
+            let block_end = block_start + usize::try_from(block.compressed_len).unwrap();
+            units.push((block_start, block_end));
+            block_start = block_end;
+        }
+
+        let part_header_size = core::mem::size_of::<PartHeader>();
+        let mut spans = Vec::new();
+        let (mut span_start, mut span_end) = units[0];
+        for &(unit_start, unit_end) in &units[1..] {
+            let tentative_len = unit_end - span_start;
+            if span_end > span_start
+                && u64::try_from(tentative_len + part_header_size).unwrap() > max_part_size
+            {
+                spans.push((span_start, span_end));
+                span_start = unit_start;
+            }
+            span_end = unit_end;
+        }
+        spans.push((span_start, span_end));
+
+        let part_count = u32::try_from(spans.len()).unwrap();
+        Ok(spans
+            .into_iter()
+            .enumerate()
+            .map(|(part_index, (start, end))| {
+                let part_header =
+                    PartHeader::new(archive_id, u32::try_from(part_index).unwrap(), part_count);
+
+                let mut part = Vec::with_capacity(part_header_size + (end - start));
+                part.extend_from_slice(bytemuck::bytes_of(&part_header));
+                part.extend_from_slice(&archive_bytes[start..end]);
+                part
+            })
+            .collect())
+    }
+
+    /// Assembles the complete archive, returning its bytes alongside the byte offset at which the
+    /// (compressed) data stream begins and the block directory describing it — both needed by
+    /// [`Self::finish_split_with`] to split the data stream on block boundaries. Returns
+    /// `Error::DuplicateName` if two entries share a name, since that would make
+    /// [`crate::Archive::get`]'s lookup ambiguous.
+    fn build<H: Digest256>(self) -> Result<(Vec<u8>, usize, Vec<BlockEntry>)> {
         let names_bytes = self.names.as_slice();
         let extra_data_bytes = self.extra_data.as_slice();
         let entries_bytes = bytemuck::cast_slice(self.table_entries.as_slice());
-        let data_bytes = self.data.as_slice();
+        let chunk_table_bytes = bytemuck::cast_slice(self.chunk_table.as_slice());
+        let chunk_index_bytes = bytemuck::cast_slice(self.chunk_index.as_slice());
+
+        // Sort entry-table indices by name, so `Archive::get` can binary search instead of
+        // linear-scanning `Archive::iter`. Insertion order (used by `iter`) is preserved in
+        // `table_entries` itself; this is a parallel, independently-ordered index.
+        let mut name_index: Vec<u32> = (0..self.table_entries.len() as u32).collect();
+        name_index.sort_by(|&a, &b| self.entry_name(a).cmp(self.entry_name(b)));
+        if name_index
+            .windows(2)
+            .any(|w| self.entry_name(w[0]) == self.entry_name(w[1]))
+        {
+            return Err(Error::DuplicateName);
+        }
+        let name_index_bytes = bytemuck::cast_slice(name_index.as_slice());
+
+        let mut compressed_data = Vec::new();
+        let mut block_entries = Vec::new();
+        for block in self.chunk_pool.chunks(BLOCK_SIZE as usize) {
+            let compressed_offset = u64::try_from(compressed_data.len()).unwrap();
+            compress_block(self.codec, block, &mut compressed_data)
+                .expect("selected codec is unavailable");
+            let compressed_len = u64::try_from(compressed_data.len()).unwrap() - compressed_offset;
+
+            block_entries.push(BlockEntry::new(
+                compressed_offset,
+                compressed_len,
+                block.len().try_into().unwrap(),
+            ));
+        }
+        let block_entries_bytes = bytemuck::cast_slice(block_entries.as_slice());
+
+        let mut digest = H::default();
+        digest.update(entries_bytes);
+        digest.update(block_entries_bytes);
+        digest.update(chunk_table_bytes);
+        digest.update(chunk_index_bytes);
+        digest.update(name_index_bytes);
+        digest.update(names_bytes);
+        digest.update(extra_data_bytes);
+        digest.update(&compressed_data);
+        let content_hash = digest.finalize();
 
         let mut archive_bytes = Vec::new();
 
@@ -54,25 +268,48 @@ impl ArchiveBuilder {
         let names_size = u64::try_from(self.names.len()).unwrap();
         let extra_data_size = u64::try_from(self.extra_data.len()).unwrap();
         let entries_size = u64::try_from(entries_bytes.len()).unwrap();
-        let data_size = u64::try_from(data_bytes.len()).unwrap();
-        let total_size = header_size + names_size + extra_data_size + entries_size + data_size;
+        let block_entries_size = u64::try_from(block_entries_bytes.len()).unwrap();
+        let chunk_table_size = u64::try_from(chunk_table_bytes.len()).unwrap();
+        let chunk_index_size = u64::try_from(chunk_index_bytes.len()).unwrap();
+        let name_index_size = u64::try_from(name_index_bytes.len()).unwrap();
+        let data_size = u64::try_from(compressed_data.len()).unwrap();
+        let total_size = header_size
+            + entries_size
+            + block_entries_size
+            + chunk_table_size
+            + chunk_index_size
+            + name_index_size
+            + names_size
+            + extra_data_size
+            + data_size;
 
         let header = ArchiveHeader::new(
-            crate::VERSION_0_0_1_0,
+            crate::VERSION_0_0_5_0,
             self.table_entries.len().try_into().unwrap(),
             self.names.len().try_into().unwrap(),
             self.extra_data.len().try_into().unwrap(),
-            total_size,
+            self.chunk_pool.len().try_into().unwrap(),
+            self.codec as u32,
+            u32::try_from(BLOCK_SIZE).unwrap(),
+            block_entries.len().try_into().unwrap(),
+            self.chunk_table.len().try_into().unwrap(),
+            self.chunk_index.len().try_into().unwrap(),
+            content_hash,
         );
 
         archive_bytes.extend_from_slice(bytemuck::bytes_of(&header));
         archive_bytes.extend_from_slice(entries_bytes);
+        archive_bytes.extend_from_slice(block_entries_bytes);
+        archive_bytes.extend_from_slice(chunk_table_bytes);
+        archive_bytes.extend_from_slice(chunk_index_bytes);
+        archive_bytes.extend_from_slice(name_index_bytes);
         archive_bytes.extend_from_slice(names_bytes);
         archive_bytes.extend_from_slice(extra_data_bytes);
-        archive_bytes.extend_from_slice(data_bytes);
+        let data_offset = archive_bytes.len();
+        archive_bytes.extend_from_slice(&compressed_data);
 
         assert_eq!(total_size, archive_bytes.len() as u64);
 
-        archive_bytes
+        Ok((archive_bytes, data_offset, block_entries))
     }
 }
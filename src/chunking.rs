@@ -0,0 +1,46 @@
+//! Gear/FastCDC-style content-defined chunking, used by [`crate::builder::ArchiveBuilder`] to
+//! split pushed entry data into dedup-friendly chunks.
+
+/// Chunks below this size are never cut early, even on a hash match.
+pub(crate) const MIN_CHUNK_SIZE: usize = 2 * 1024;
+/// Chunks are force-cut once they reach this size, regardless of the rolling hash.
+pub(crate) const MAX_CHUNK_SIZE: usize = 64 * 1024;
+/// Targets an average chunk size of ~8 KiB.
+const MASK: u64 = (1 << 13) - 1;
+
+const fn build_gear_table() -> [u64; 256] {
+    // A fixed xorshift64 stream seeded with a constant, used only to fill the gear table with
+    // well-distributed bits; not used for anything security-sensitive.
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut table = [0u64; 256];
+    let mut i = 0;
+    while i < 256 {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        table[i] = state;
+        i += 1;
+    }
+    table
+}
+
+const GEAR: [u64; 256] = build_gear_table();
+
+/// Returns the next content-defined chunk at the start of `data`. Always returns a non-empty
+/// slice when `data` is non-empty.
+pub(crate) fn next_chunk(data: &[u8]) -> &[u8] {
+    if data.len() <= MIN_CHUNK_SIZE {
+        return data;
+    }
+
+    let max = data.len().min(MAX_CHUNK_SIZE);
+    let mut hash: u64 = 0;
+    for i in MIN_CHUNK_SIZE..max {
+        hash = (hash << 1).wrapping_add(GEAR[data[i] as usize]);
+        if hash & MASK == 0 {
+            return &data[..=i];
+        }
+    }
+
+    &data[..max]
+}
@@ -0,0 +1,92 @@
+use crate::{Error, Result};
+
+/// Size, in bytes, of a single uncompressed block before codec compression is applied.
+pub const BLOCK_SIZE: u64 = 64 * 1024;
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Store = 0,
+    Zstd = 1,
+    Lzma = 2,
+}
+
+impl TryFrom<u32> for Codec {
+    type Error = Error;
+
+    fn try_from(value: u32) -> Result<Self> {
+        match value {
+            0 => Ok(Codec::Store),
+            1 => Ok(Codec::Zstd),
+            2 => Ok(Codec::Lzma),
+            _ => Err(Error::InvalidCodec),
+        }
+    }
+}
+
+/// Compresses `block` with `codec`, appending the result to `out`.
+#[cfg(feature = "alloc")]
+pub fn compress_block(codec: Codec, block: &[u8], out: &mut alloc::vec::Vec<u8>) -> Result<()> {
+    match codec {
+        Codec::Store => {
+            out.extend_from_slice(block);
+            Ok(())
+        }
+
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => {
+            let compressed = zstd::bulk::compress(block, 0).map_err(|_| Error::CompressionFailed)?;
+            out.extend_from_slice(&compressed);
+            Ok(())
+        }
+        #[cfg(not(feature = "zstd"))]
+        Codec::Zstd => Err(Error::UnsupportedCodec),
+
+        #[cfg(feature = "lzma")]
+        Codec::Lzma => {
+            let mut compressed = alloc::vec::Vec::new();
+            lzma_rs::lzma_compress(&mut &block[..], &mut compressed)
+                .map_err(|_| Error::CompressionFailed)?;
+            out.extend_from_slice(&compressed);
+            Ok(())
+        }
+        #[cfg(not(feature = "lzma"))]
+        Codec::Lzma => Err(Error::UnsupportedCodec),
+    }
+}
+
+/// Decompresses `block` (compressed with `codec`) into `out`, which must be at least
+/// `uncompressed_len` bytes. Returns the number of bytes written.
+pub fn decompress_block(codec: Codec, block: &[u8], out: &mut [u8]) -> Result<usize> {
+    match codec {
+        Codec::Store => {
+            let len = block.len();
+            out.get_mut(..len)
+                .ok_or(Error::BufferTooSmall)?
+                .copy_from_slice(block);
+            Ok(len)
+        }
+
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => {
+            let written = zstd::bulk::decompress_to_buffer(block, out)
+                .map_err(|_| Error::DecompressionFailed)?;
+            Ok(written)
+        }
+        #[cfg(not(feature = "zstd"))]
+        Codec::Zstd => Err(Error::UnsupportedCodec),
+
+        #[cfg(feature = "lzma")]
+        Codec::Lzma => {
+            let mut decompressed = alloc::vec::Vec::new();
+            lzma_rs::lzma_decompress(&mut &block[..], &mut decompressed)
+                .map_err(|_| Error::DecompressionFailed)?;
+            out.get_mut(..decompressed.len())
+                .ok_or(Error::BufferTooSmall)?
+                .copy_from_slice(&decompressed);
+            Ok(decompressed.len())
+        }
+        #[cfg(not(feature = "lzma"))]
+        Codec::Lzma => Err(Error::UnsupportedCodec),
+    }
+}
@@ -0,0 +1,62 @@
+//! Slice-by-8 CRC-32 (ISO-3309, reflected, same polynomial as `zlib`/`gzip`), used for per-entry
+//! data integrity checks. Implemented from scratch so the core crate can stay `no_std` without
+//! pulling in a CRC dependency.
+
+const POLY: u32 = 0xEDB8_8320;
+
+const fn build_tables() -> [[u32; 256]; 8] {
+    let mut tables = [[0u32; 256]; 8];
+
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ POLY } else { crc >> 1 };
+            bit += 1;
+        }
+        tables[0][i] = crc;
+        i += 1;
+    }
+
+    let mut slice = 1;
+    while slice < 8 {
+        let mut i = 0;
+        while i < 256 {
+            let previous = tables[slice - 1][i];
+            tables[slice][i] = (previous >> 8) ^ tables[0][(previous & 0xFF) as usize];
+            i += 1;
+        }
+        slice += 1;
+    }
+
+    tables
+}
+
+const TABLES: [[u32; 256]; 8] = build_tables();
+
+/// Computes the CRC-32 checksum of `data`.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        let one = u32::from_le_bytes(chunk[0..4].try_into().unwrap()) ^ crc;
+        let two = u32::from_le_bytes(chunk[4..8].try_into().unwrap());
+
+        crc = TABLES[7][(one & 0xFF) as usize]
+            ^ TABLES[6][((one >> 8) & 0xFF) as usize]
+            ^ TABLES[5][((one >> 16) & 0xFF) as usize]
+            ^ TABLES[4][((one >> 24) & 0xFF) as usize]
+            ^ TABLES[3][(two & 0xFF) as usize]
+            ^ TABLES[2][((two >> 8) & 0xFF) as usize]
+            ^ TABLES[1][((two >> 16) & 0xFF) as usize]
+            ^ TABLES[0][((two >> 24) & 0xFF) as usize];
+    }
+
+    for &byte in chunks.remainder() {
+        crc = TABLES[0][((crc ^ u32::from(byte)) & 0xFF) as usize] ^ (crc >> 8);
+    }
+
+    !crc
+}
@@ -0,0 +1,50 @@
+//! Pluggable whole-archive digest used by [`crate::Archive::verify`]. The core crate ships a
+//! simple, dependency-free default; consumers who need a cryptographic guarantee can supply their
+//! own implementation (e.g. wrapping the `sha2` crate's `Sha256`).
+use core::mem::size_of;
+
+/// Produces a 256-bit digest over one or more byte slices, fed in order.
+pub trait Digest256: Default {
+    fn update(&mut self, data: &[u8]);
+    fn finalize(self) -> [u8; 32];
+}
+
+/// Non-cryptographic default digest (splitmix64-derived), sufficient for detecting accidental
+/// corruption but not for any adversarial integrity guarantee.
+#[derive(Default)]
+pub struct SimpleDigest {
+    lanes: [u64; 4],
+}
+
+impl SimpleDigest {
+    fn mix(lane: &mut u64, word: u64) {
+        *lane ^= word;
+        *lane = lane.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        *lane ^= *lane >> 29;
+    }
+}
+
+impl Digest256 for SimpleDigest {
+    fn update(&mut self, data: &[u8]) {
+        let mut chunks = data.chunks_exact(size_of::<u64>());
+        for (i, chunk) in (&mut chunks).enumerate() {
+            let word = u64::from_le_bytes(chunk.try_into().unwrap());
+            Self::mix(&mut self.lanes[i % 4], word);
+        }
+
+        let remainder = chunks.remainder();
+        if !remainder.is_empty() {
+            let mut buf = [0u8; 8];
+            buf[..remainder.len()].copy_from_slice(remainder);
+            Self::mix(&mut self.lanes[3], u64::from_le_bytes(buf));
+        }
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (i, lane) in self.lanes.into_iter().enumerate() {
+            out[i * 8..(i + 1) * 8].copy_from_slice(&lane.to_le_bytes());
+        }
+        out
+    }
+}
@@ -9,14 +9,31 @@ extern crate alloc;
 
 #[cfg(feature = "alloc")]
 pub mod builder;
+#[cfg(feature = "alloc")]
+pub(crate) mod chunking;
+pub mod compress;
+pub mod crc32;
+pub mod hash;
 
+use compress::{decompress_block, Codec};
 use core::mem::size_of;
+use hash::{Digest256, SimpleDigest};
 
 pub const MAGIC: &[u8; 8] = b"OARCHIVE";
 
 pub const VERSION_0_0_1_0: u32 = u32::from_le_bytes([0, 0, 1, 0]);
+pub const VERSION_0_0_2_0: u32 = u32::from_le_bytes([0, 0, 2, 0]);
+pub const VERSION_0_0_3_0: u32 = u32::from_le_bytes([0, 0, 3, 0]);
+pub const VERSION_0_0_4_0: u32 = u32::from_le_bytes([0, 0, 4, 0]);
+pub const VERSION_0_0_5_0: u32 = u32::from_le_bytes([0, 0, 5, 0]);
 
-pub const VERSIONS: [u32; 1] = [VERSION_0_0_1_0];
+pub const VERSIONS: [u32; 5] = [
+    VERSION_0_0_1_0,
+    VERSION_0_0_2_0,
+    VERSION_0_0_3_0,
+    VERSION_0_0_4_0,
+    VERSION_0_0_5_0,
+];
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Error {
@@ -28,6 +45,23 @@ pub enum Error {
     InvalidSizeSum,
     IncompleteData,
     InvalidEntryTable,
+    InvalidBlockDirectory,
+    InvalidChunkTable,
+    InvalidChunkRef,
+    InvalidNameIndex,
+    DuplicateName,
+    MissingPart,
+    PartMismatch,
+    EntryOutOfBounds,
+    InvalidUtf8,
+    InvalidCodec,
+    UnsupportedCodec,
+    CompressionFailed,
+    DecompressionFailed,
+    BufferTooSmall,
+    ChecksumMismatch,
+    CorruptEntry,
+    DataNotResident,
 }
 
 impl core::fmt::Display for Error {
@@ -40,26 +74,74 @@ impl core::error::Error for Error {}
 
 pub type Result<T> = core::result::Result<T, Error>;
 
+/// Computes `count * size_of::<T>()`, reporting `err` on a `usize` conversion or multiplication
+/// overflow instead of panicking (as either would on a maliciously large `count`).
+fn checked_region_size<T>(count: u32, err: Error) -> Result<usize> {
+    usize::try_from(count)
+        .ok()
+        .and_then(|count| count.checked_mul(size_of::<T>()))
+        .ok_or(err)
+}
+
 impl<'a> Archive<'a> {
     pub fn new(data: &'a [u8]) -> Result<Self> {
-        // TODO pre-handle panic conditions for `split_at`
-
-        let (header_bytes, data) = data.split_at(size_of::<ArchiveHeader>());
+        let (header_bytes, data) = data
+            .split_at_checked(size_of::<ArchiveHeader>())
+            .ok_or(Error::IncompleteHeader)?;
         let header = <&ArchiveHeader>::try_from(header_bytes)?;
 
-        let entry_table_size =
-            usize::try_from(header.entry_count).unwrap() * size_of::<ArchiveTableEntry>();
-        let (entry_table_bytes, data) = data.split_at(entry_table_size);
+        let entry_table_size = checked_region_size::<ArchiveTableEntry>(
+            header.entry_count,
+            Error::InvalidEntryTable,
+        )?;
+        let (entry_table_bytes, data) =
+            data.split_at_checked(entry_table_size).ok_or(Error::IncompleteData)?;
         let entry_table = bytemuck::checked::try_cast_slice(entry_table_bytes)
             .map_err(|_| Error::InvalidEntryTable)?;
 
-        let (names_bytes, data) = data.split_at(usize::try_from(header.names_size).unwrap());
+        let block_directory_size =
+            checked_region_size::<BlockEntry>(header.block_count, Error::InvalidBlockDirectory)?;
+        let (block_directory_bytes, data) =
+            data.split_at_checked(block_directory_size).ok_or(Error::IncompleteData)?;
+        let block_directory = bytemuck::try_cast_slice(block_directory_bytes)
+            .map_err(|_| Error::InvalidBlockDirectory)?;
+
+        let chunk_table_size =
+            checked_region_size::<ChunkEntry>(header.chunk_count, Error::InvalidChunkTable)?;
+        let (chunk_table_bytes, data) =
+            data.split_at_checked(chunk_table_size).ok_or(Error::IncompleteData)?;
+        let chunk_table =
+            bytemuck::try_cast_slice(chunk_table_bytes).map_err(|_| Error::InvalidChunkTable)?;
+
+        let chunk_index_size =
+            checked_region_size::<u32>(header.chunk_index_count, Error::InvalidChunkTable)?;
+        let (chunk_index_bytes, data) =
+            data.split_at_checked(chunk_index_size).ok_or(Error::IncompleteData)?;
+        let chunk_index =
+            bytemuck::try_cast_slice(chunk_index_bytes).map_err(|_| Error::InvalidChunkTable)?;
+
+        let name_index_size =
+            checked_region_size::<u32>(header.entry_count, Error::InvalidNameIndex)?;
+        let (name_index_bytes, data) =
+            data.split_at_checked(name_index_size).ok_or(Error::IncompleteData)?;
+        let name_index =
+            bytemuck::try_cast_slice(name_index_bytes).map_err(|_| Error::InvalidNameIndex)?;
+
+        let names_size = usize::try_from(header.names_size).map_err(|_| Error::IncompleteData)?;
+        let (names_bytes, data) = data.split_at_checked(names_size).ok_or(Error::IncompleteData)?;
+
+        let extra_data_size =
+            usize::try_from(header.extra_data_size).map_err(|_| Error::IncompleteData)?;
         let (extra_data_bytes, data) =
-            data.split_at(usize::try_from(header.extra_data_size).unwrap());
+            data.split_at_checked(extra_data_size).ok_or(Error::IncompleteData)?;
 
         Ok(Self {
             header,
             entry_table,
+            block_directory,
+            chunk_table,
+            chunk_index,
+            name_index,
             names: names_bytes,
             extra_data: extra_data_bytes,
             data,
@@ -69,12 +151,247 @@ impl<'a> Archive<'a> {
     pub fn iter(&self) -> ArchiveIterator {
         ArchiveIterator {
             entries: self.entry_table,
+            chunk_table: self.chunk_table,
+            chunk_index: self.chunk_index,
+            codec: Codec::try_from(self.header.codec).unwrap_or(Codec::Store),
             names: self.names,
             extra_data: self.extra_data,
             data: self.data,
             index: 0,
         }
     }
+
+    /// Looks up an entry by name via binary search over the archive's sorted name index,
+    /// built by [`crate::builder::ArchiveBuilder::finish`]. Runs in `O(log n)` time, unlike the
+    /// `O(n)` linear scan of [`Self::iter`]. Names are unique within an archive (`finish` rejects
+    /// duplicates with `Error::DuplicateName`), so a match is unambiguous. Returns `Ok(None)` if
+    /// no entry has that name, and `Err` if the matching entry's table data is malformed.
+    pub fn get(&self, name: &str) -> Result<Option<ArchiveEntry<'a>>> {
+        let pos = match self.name_index.binary_search_by(|&table_index| {
+            self.entry_name(table_index as usize)
+                .map(|entry_name| entry_name.cmp(name))
+                .unwrap_or(core::cmp::Ordering::Greater)
+        }) {
+            Ok(pos) => pos,
+            Err(_) => return Ok(None),
+        };
+
+        self.get_by_index(self.name_index[pos] as usize)
+    }
+
+    /// Looks up an entry by its position in the entry table, i.e. the order entries were pushed
+    /// to the [`crate::builder::ArchiveBuilder`] (the same order [`Self::iter`] yields them in).
+    /// Returns `Ok(None)` if `index` is out of range, and `Err` if the entry's table data is
+    /// malformed.
+    pub fn get_by_index(&self, index: usize) -> Result<Option<ArchiveEntry<'a>>> {
+        let Some(table_entry) = self.entry_table.get(index) else {
+            return Ok(None);
+        };
+
+        ArchiveEntry::from_table_entry(
+            table_entry,
+            Codec::try_from(self.header.codec).unwrap_or(Codec::Store),
+            self.chunk_table,
+            self.chunk_index,
+            self.names,
+            self.extra_data,
+            self.data,
+        )
+        .map(Some)
+    }
+
+    /// Reads the (not yet bounds/UTF-8-validated-against-other-fields) name of the entry at
+    /// `table_index`, returning `None` instead of panicking on out-of-range or non-UTF-8 bytes.
+    fn entry_name(&self, table_index: usize) -> Option<&'a str> {
+        let table_entry = self.entry_table.get(table_index)?;
+        let name_offset = usize::try_from(table_entry.name_offset).ok()?;
+        let name_len = usize::try_from(table_entry.name_len).ok()?;
+        let name_bytes = self.names.get(name_offset..name_offset.checked_add(name_len)?)?;
+
+        core::str::from_utf8(name_bytes).ok()
+    }
+
+    /// Decompresses the blocks backing the logical data range `start..end` of the (possibly
+    /// block-compressed) data stream into `out`, which must be exactly `end - start` bytes.
+    /// `block_scratch` is reused as intermediate storage for each compressed block and must be
+    /// at least `self.block_size()` bytes.
+    fn decompress_range(
+        &self,
+        codec: Codec,
+        start: u64,
+        end: u64,
+        out: &mut [u8],
+        block_scratch: &mut [u8],
+    ) -> Result<usize> {
+        let mut written = 0;
+        let mut block_start = 0u64;
+
+        for block in self.block_directory {
+            let block_end = block_start
+                .checked_add(block.uncompressed_len)
+                .ok_or(Error::InvalidBlockDirectory)?;
+            if block_start >= end {
+                break;
+            }
+            if block_end <= start {
+                block_start = block_end;
+                continue;
+            }
+
+            let compressed_offset =
+                usize::try_from(block.compressed_offset).map_err(|_| Error::InvalidBlockDirectory)?;
+            let compressed_len =
+                usize::try_from(block.compressed_len).map_err(|_| Error::InvalidBlockDirectory)?;
+            let compressed_end = compressed_offset
+                .checked_add(compressed_len)
+                .ok_or(Error::InvalidBlockDirectory)?;
+            let compressed_bytes = self
+                .data
+                .get(compressed_offset..compressed_end)
+                .ok_or(Error::IncompleteData)?;
+
+            let uncompressed_len = decompress_block(codec, compressed_bytes, block_scratch)?;
+            let block_bytes = &block_scratch[..uncompressed_len];
+
+            let overlap_start = start.max(block_start);
+            let overlap_end = end.min(
+                block_start
+                    .checked_add(uncompressed_len as u64)
+                    .ok_or(Error::InvalidBlockDirectory)?,
+            );
+            // The block decompressed to fewer bytes than the directory records for it.
+            if overlap_end < overlap_start {
+                return Err(Error::InvalidBlockDirectory);
+            }
+
+            let src_start =
+                usize::try_from(overlap_start - block_start).map_err(|_| Error::InvalidBlockDirectory)?;
+            let src_end =
+                usize::try_from(overlap_end - block_start).map_err(|_| Error::InvalidBlockDirectory)?;
+            let dst_start =
+                usize::try_from(overlap_start - start).map_err(|_| Error::InvalidBlockDirectory)?;
+            let dst_end =
+                usize::try_from(overlap_end - start).map_err(|_| Error::InvalidBlockDirectory)?;
+
+            let src = block_bytes.get(src_start..src_end).ok_or(Error::InvalidBlockDirectory)?;
+            out.get_mut(dst_start..dst_end)
+                .ok_or(Error::BufferTooSmall)?
+                .copy_from_slice(src);
+
+            written = written.max(dst_end);
+            block_start = block_end;
+        }
+
+        Ok(written)
+    }
+
+    /// Reassembles `entry`'s data by concatenating its referenced chunks, decompressing each
+    /// one's backing blocks on the fly, and copying the result into `out` (which must be exactly
+    /// `entry.len()` bytes). `block_scratch` is reused as intermediate storage for each
+    /// compressed block and must be at least `self.block_size()` bytes.
+    pub fn copy_entry_into(
+        &self,
+        entry: &ArchiveEntry,
+        out: &mut [u8],
+        block_scratch: &mut [u8],
+    ) -> Result<usize> {
+        let codec = Codec::try_from(self.header.codec)?;
+
+        let mut written: usize = 0;
+        for &chunk_index in entry.chunk_refs {
+            let chunk = self
+                .chunk_table
+                .get(chunk_index as usize)
+                .ok_or(Error::InvalidChunkRef)?;
+
+            let start = chunk.pool_offset;
+            let end = start.checked_add(chunk.pool_len).ok_or(Error::InvalidChunkRef)?;
+            let chunk_len = usize::try_from(chunk.pool_len).map_err(|_| Error::InvalidChunkRef)?;
+
+            let new_written = written.checked_add(chunk_len).ok_or(Error::BufferTooSmall)?;
+            let dst = out.get_mut(written..new_written).ok_or(Error::BufferTooSmall)?;
+            let dst_len = dst.len();
+            if self.decompress_range(codec, start, end, dst, block_scratch)? != dst_len {
+                return Err(Error::IncompleteData);
+            }
+
+            written = new_written;
+        }
+
+        Ok(written)
+    }
+
+    /// Hashes the entry table, block directory, chunk table, chunk index, name index, names,
+    /// extra data, and (compressed) data regions with the default, dependency-free digest and compares the result against the archive's
+    /// recorded `content_hash`. Use [`Self::verify_with`] to supply a stronger digest (e.g.
+    /// SHA-256).
+    pub fn verify(&self) -> Result<()> {
+        self.verify_with::<SimpleDigest>()
+    }
+
+    pub fn verify_with<H: Digest256>(&self) -> Result<()> {
+        let mut digest = H::default();
+        digest.update(bytemuck::cast_slice(self.entry_table));
+        digest.update(bytemuck::cast_slice(self.block_directory));
+        digest.update(bytemuck::cast_slice(self.chunk_table));
+        digest.update(bytemuck::cast_slice(self.chunk_index));
+        digest.update(bytemuck::cast_slice(self.name_index));
+        digest.update(self.names);
+        digest.update(self.extra_data);
+        digest.update(self.data);
+
+        if &digest.finalize() == self.header.content_hash() {
+            Ok(())
+        } else {
+            Err(Error::ChecksumMismatch)
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> Archive<'a> {
+    /// Reconstructs an [`Archive`] spanning the parts produced by
+    /// [`crate::builder::ArchiveBuilder::finish_split`]. `parts` must be given in `part_index`
+    /// order and share a single `archive_id` (`Error::PartMismatch` otherwise; a missing or
+    /// reordered part yields `Error::MissingPart`). `buffer` is cleared and filled with the parts'
+    /// payloads joined back into one contiguous logical archive, and must outlive the returned
+    /// `Archive`.
+    pub fn from_parts(parts: &[&[u8]], buffer: &'a mut alloc::vec::Vec<u8>) -> Result<Self> {
+        if parts.is_empty() {
+            return Err(Error::MissingPart);
+        }
+
+        let part_count = u32::try_from(parts.len()).unwrap();
+        let mut archive_id: Option<[u8; 16]> = None;
+
+        buffer.clear();
+        for (part_index, part) in parts.iter().enumerate() {
+            let (header_bytes, payload) = part
+                .split_at_checked(size_of::<PartHeader>())
+                .ok_or(Error::IncompleteHeader)?;
+            let header = bytemuck::try_from_bytes::<PartHeader>(header_bytes)
+                .map_err(|_| Error::IncompleteHeader)?;
+
+            match archive_id {
+                None => archive_id = Some(*header.archive_id()),
+                Some(id) if id == *header.archive_id() => {}
+                Some(_) => return Err(Error::PartMismatch),
+            }
+            if header.part_count() > part_count {
+                return Err(Error::MissingPart);
+            }
+            if header.part_count() != part_count {
+                return Err(Error::PartMismatch);
+            }
+            if header.part_index() != u32::try_from(part_index).unwrap() {
+                return Err(Error::MissingPart);
+            }
+
+            buffer.extend_from_slice(payload);
+        }
+
+        Self::new(buffer.as_slice())
+    }
 }
 
 impl core::fmt::Debug for Archive<'_> {
@@ -87,6 +404,9 @@ impl core::fmt::Debug for Archive<'_> {
             .field("Extra Data Size", &self.extra_data_size())
             .field("Extra Data Bytes", &self.extra_data)
             .field("Uncompressed Size", &self.uncompressed_size())
+            .field("Codec", &self.codec())
+            .field("Block Count", &self.block_count())
+            .field("Chunk Count", &self.chunk_count())
             .field("Data Bytes", &self.data)
             .finish()
     }
@@ -101,6 +421,13 @@ pub struct ArchiveHeader {
     names_size: u64,
     extra_data_size: u64,
     uncompressed_size: u64,
+    codec: u32,
+    block_size: u32,
+    block_count: u32,
+    _reserved: u32,
+    chunk_count: u32,
+    chunk_index_count: u32,
+    content_hash: [u8; 32],
 }
 
 unsafe impl bytemuck::Zeroable for ArchiveHeader {}
@@ -124,12 +451,19 @@ impl<'a> TryFrom<&'a [u8]> for &'a ArchiveHeader {
 }
 
 impl ArchiveHeader {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         version: u32,
         entry_count: u32,
         names_size: u64,
         extra_data_size: u64,
         uncompressed_size: u64,
+        codec: u32,
+        block_size: u32,
+        block_count: u32,
+        chunk_count: u32,
+        chunk_index_count: u32,
+        content_hash: [u8; 32],
     ) -> Self {
         ArchiveHeader {
             _magic: *MAGIC,
@@ -138,6 +472,13 @@ impl ArchiveHeader {
             names_size,
             extra_data_size,
             uncompressed_size,
+            codec,
+            block_size,
+            block_count,
+            _reserved: 0,
+            chunk_count,
+            chunk_index_count,
+            content_hash,
         }
     }
 
@@ -165,6 +506,179 @@ impl ArchiveHeader {
     pub const fn uncompressed_size(&self) -> u64 {
         self.uncompressed_size
     }
+
+    #[inline]
+    pub const fn codec(&self) -> u32 {
+        self.codec
+    }
+
+    #[inline]
+    pub const fn block_size(&self) -> u32 {
+        self.block_size
+    }
+
+    #[inline]
+    pub const fn block_count(&self) -> u32 {
+        self.block_count
+    }
+
+    #[inline]
+    pub const fn chunk_count(&self) -> u32 {
+        self.chunk_count
+    }
+
+    #[inline]
+    pub const fn chunk_index_count(&self) -> u32 {
+        self.chunk_index_count
+    }
+
+    #[inline]
+    pub const fn content_hash(&self) -> &[u8; 32] {
+        &self.content_hash
+    }
+}
+
+/// Prefixes each part of a split archive produced by
+/// [`crate::builder::ArchiveBuilder::finish_split`], identifying the part's position among its
+/// siblings. The part's payload (the rest of the part's bytes, after this header) is otherwise
+/// opaque here: part 0's payload begins with a complete [`ArchiveHeader`] and its metadata
+/// regions, while every other part's payload is a pure continuation of the (compressed) data
+/// stream.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PartHeader {
+    archive_id: [u8; 16],
+    part_index: u32,
+    part_count: u32,
+}
+
+unsafe impl bytemuck::Zeroable for PartHeader {}
+unsafe impl bytemuck::Pod for PartHeader {}
+
+impl PartHeader {
+    pub(crate) fn new(archive_id: [u8; 16], part_index: u32, part_count: u32) -> Self {
+        Self {
+            archive_id,
+            part_index,
+            part_count,
+        }
+    }
+
+    #[inline]
+    pub const fn archive_id(&self) -> &[u8; 16] {
+        &self.archive_id
+    }
+
+    #[inline]
+    pub const fn part_index(&self) -> u32 {
+        self.part_index
+    }
+
+    #[inline]
+    pub const fn part_count(&self) -> u32 {
+        self.part_count
+    }
+}
+
+/// An entry in the block directory, describing one compressed block of the logical data stream.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BlockEntry {
+    compressed_offset: u64,
+    compressed_len: u64,
+    uncompressed_len: u64,
+}
+
+unsafe impl bytemuck::Zeroable for BlockEntry {}
+unsafe impl bytemuck::Pod for BlockEntry {}
+
+impl BlockEntry {
+    pub(crate) fn new(compressed_offset: u64, compressed_len: u64, uncompressed_len: u64) -> Self {
+        Self {
+            compressed_offset,
+            compressed_len,
+            uncompressed_len,
+        }
+    }
+}
+
+/// An entry in the global, deduplicated chunk pool, describing one unique chunk's position
+/// within the logical (block-compressed) data stream.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ChunkEntry {
+    pool_offset: u64,
+    pool_len: u64,
+}
+
+unsafe impl bytemuck::Zeroable for ChunkEntry {}
+unsafe impl bytemuck::Pod for ChunkEntry {}
+
+impl ChunkEntry {
+    pub(crate) fn new(pool_offset: u64, pool_len: u64) -> Self {
+        Self {
+            pool_offset,
+            pool_len,
+        }
+    }
+}
+
+/// Fixed-size POSIX metadata for an entry, stored at the start of its `extra_data` region.
+/// Variable-length payloads (e.g. a symlink target) follow immediately after, for the remainder
+/// of `extra_data`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct EntryMetadata {
+    mtime: u64,
+    mode: u32,
+    uid: u32,
+    gid: u32,
+    device_major: u32,
+    device_minor: u32,
+    _reserved: u32,
+}
+
+unsafe impl bytemuck::Zeroable for EntryMetadata {}
+unsafe impl bytemuck::Pod for EntryMetadata {}
+
+impl EntryMetadata {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(mode: u32, uid: u32, gid: u32, mtime: u64, device_major: u32, device_minor: u32) -> Self {
+        Self {
+            mtime,
+            mode,
+            uid,
+            gid,
+            device_major,
+            device_minor,
+            _reserved: 0,
+        }
+    }
+
+    #[inline]
+    pub const fn mode(&self) -> u32 {
+        self.mode
+    }
+
+    #[inline]
+    pub const fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    #[inline]
+    pub const fn gid(&self) -> u32 {
+        self.gid
+    }
+
+    #[inline]
+    pub const fn mtime(&self) -> u64 {
+        self.mtime
+    }
+
+    #[inline]
+    pub const fn device_ids(&self) -> (u32, u32) {
+        (self.device_major, self.device_minor)
+    }
 }
 
 #[repr(u32)]
@@ -172,6 +686,10 @@ impl ArchiveHeader {
 pub enum Signature {
     File = 0,
     Directory = 1,
+    Symlink = 2,
+    CharDevice = 3,
+    BlockDevice = 4,
+    Fifo = 5,
 
     OS(u32) = u32::MAX,
 }
@@ -182,7 +700,7 @@ unsafe impl bytemuck::CheckedBitPattern for Signature {
     fn is_valid_bit_pattern(bits: &Self::Bits) -> bool {
         matches!(
             u32::from_le_bytes(bits[..size_of::<u32>()].try_into().unwrap()),
-            0 | 1 | u32::MAX
+            0 | 1 | 2 | 3 | 4 | 5 | u32::MAX
         )
     }
 }
@@ -195,8 +713,12 @@ pub(crate) struct ArchiveTableEntry {
     name_len: u64,
     extra_data_offset: u64,
     extra_data_len: u64,
-    data_offset: u64,
-    data_len: u64,
+    /// Offset into the archive's flat chunk-index region of this entry's first chunk reference.
+    chunk_ref_offset: u64,
+    /// Number of (ordered) chunk references belonging to this entry.
+    chunk_ref_count: u64,
+    data_crc32: u32,
+    _reserved: u32,
 }
 
 unsafe impl bytemuck::NoUninit for ArchiveTableEntry {}
@@ -211,14 +733,16 @@ unsafe impl bytemuck::CheckedBitPattern for ArchiveTableEntry {
 }
 
 impl ArchiveTableEntry {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         signature: Signature,
         name_offset: u64,
         name_len: u64,
         extra_data_offset: u64,
         extra_data_len: u64,
-        data_offset: u64,
-        data_len: u64,
+        chunk_ref_offset: u64,
+        chunk_ref_count: u64,
+        data_crc32: u32,
     ) -> Self {
         Self {
             signature,
@@ -226,18 +750,34 @@ impl ArchiveTableEntry {
             name_len,
             extra_data_offset,
             extra_data_len,
-            data_offset,
-            data_len,
+            chunk_ref_offset,
+            chunk_ref_count,
+            data_crc32,
+            _reserved: 0,
         }
     }
+
+    #[inline]
+    pub(crate) fn name_offset(&self) -> u64 {
+        self.name_offset
+    }
+
+    #[inline]
+    pub(crate) fn name_len(&self) -> u64 {
+        self.name_len
+    }
 }
 
 pub struct Archive<'a> {
     header: &'a ArchiveHeader,
     entry_table: &'a [ArchiveTableEntry],
+    block_directory: &'a [BlockEntry],
+    chunk_table: &'a [ChunkEntry],
+    chunk_index: &'a [u32],
+    /// Entry-table indices, sorted by entry name, enabling `O(log n)` lookup via [`Self::get`].
+    name_index: &'a [u32],
     names: &'a [u8],
     extra_data: &'a [u8],
-    // TODO archive 'blocks' for easier streaming decompression
     data: &'a [u8],
 }
 
@@ -250,38 +790,90 @@ impl core::ops::Deref for Archive<'_> {
 }
 
 pub struct ArchiveEntry<'a> {
+    signature: Signature,
     name: &'a str,
     extra_data: &'a [u8],
+    /// This entry's ordered chunk references, each an index into the archive's chunk table.
+    chunk_refs: &'a [u32],
+    data_len: u64,
+    data_crc32: u32,
     data: &'a [u8],
 }
 
 impl<'a> ArchiveEntry<'a> {
     fn from_table_entry(
         table_entry: &'a ArchiveTableEntry,
+        codec: Codec,
+        chunk_table: &'a [ChunkEntry],
+        chunk_index: &'a [u32],
         names: &'a [u8],
         extra_data: &'a [u8],
         data: &'a [u8],
-    ) -> Self {
-        let name_offset = usize::try_from(table_entry.name_offset).unwrap();
-        let name_len = usize::try_from(table_entry.name_len).unwrap();
-        let extra_data_offset = usize::try_from(table_entry.extra_data_offset).unwrap();
-        let extra_data_len = usize::try_from(table_entry.extra_data_len).unwrap();
-        let data_offset = usize::try_from(table_entry.data_offset).unwrap();
-        let data_len = usize::try_from(table_entry.data_len).unwrap();
-
-        let name_range = name_offset..(name_offset + name_len);
-        let extra_data_range = extra_data_offset..(extra_data_offset + extra_data_len);
-        let data_range = data_offset..(data_offset + data_len);
-
-        let name_bytes = &names[name_range];
-        let extra_data_bytes = &extra_data[extra_data_range];
-        let data_bytes = &data[data_range];
-
-        ArchiveEntry {
-            name: core::str::from_utf8(name_bytes).expect("table entry has invalid UTF-8 bytes"),
+    ) -> Result<Self> {
+        let name_offset = usize::try_from(table_entry.name_offset).map_err(|_| Error::EntryOutOfBounds)?;
+        let name_len = usize::try_from(table_entry.name_len).map_err(|_| Error::EntryOutOfBounds)?;
+        let extra_data_offset =
+            usize::try_from(table_entry.extra_data_offset).map_err(|_| Error::EntryOutOfBounds)?;
+        let extra_data_len =
+            usize::try_from(table_entry.extra_data_len).map_err(|_| Error::EntryOutOfBounds)?;
+        let chunk_ref_offset =
+            usize::try_from(table_entry.chunk_ref_offset).map_err(|_| Error::EntryOutOfBounds)?;
+        let chunk_ref_count =
+            usize::try_from(table_entry.chunk_ref_count).map_err(|_| Error::EntryOutOfBounds)?;
+
+        let name_range = name_offset
+            ..name_offset
+                .checked_add(name_len)
+                .ok_or(Error::EntryOutOfBounds)?;
+        let extra_data_range = extra_data_offset
+            ..extra_data_offset
+                .checked_add(extra_data_len)
+                .ok_or(Error::EntryOutOfBounds)?;
+        let chunk_ref_range = chunk_ref_offset
+            ..chunk_ref_offset
+                .checked_add(chunk_ref_count)
+                .ok_or(Error::EntryOutOfBounds)?;
+
+        let name_bytes = names.get(name_range).ok_or(Error::EntryOutOfBounds)?;
+        let extra_data_bytes = extra_data.get(extra_data_range).ok_or(Error::EntryOutOfBounds)?;
+        let chunk_refs = chunk_index.get(chunk_ref_range).ok_or(Error::EntryOutOfBounds)?;
+
+        let mut data_len: u64 = 0;
+        for &index in chunk_refs {
+            let chunk = chunk_table.get(index as usize).ok_or(Error::InvalidChunkRef)?;
+            data_len = data_len.checked_add(chunk.pool_len).ok_or(Error::EntryOutOfBounds)?;
+        }
+
+        // When the data stream isn't block-compressed and the entry is a single chunk, its data
+        // sits contiguously in the pool and can be borrowed directly; otherwise callers must go
+        // through `Archive::copy_entry_into` to reassemble and/or decompress it.
+        let data_bytes = match (codec, chunk_refs) {
+            (Codec::Store, [index]) => {
+                let chunk = &chunk_table[*index as usize];
+                let pool_offset = usize::try_from(chunk.pool_offset).map_err(|_| Error::EntryOutOfBounds)?;
+                let pool_len = usize::try_from(chunk.pool_len).map_err(|_| Error::EntryOutOfBounds)?;
+                data.get(pool_offset..)
+                    .and_then(|tail| tail.get(..pool_len))
+                    .ok_or(Error::EntryOutOfBounds)?
+            }
+            _ => &[],
+        };
+
+        Ok(ArchiveEntry {
+            signature: table_entry.signature,
+            name: core::str::from_utf8(name_bytes).map_err(|_| Error::InvalidUtf8)?,
             extra_data: extra_data_bytes,
+            chunk_refs,
+            data_len,
+            data_crc32: table_entry.data_crc32,
             data: data_bytes,
-        }
+        })
+    }
+
+    /// The entry's type: `File`, `Directory`, `Symlink`, a device node, etc.
+    #[inline]
+    pub const fn signature(&self) -> Signature {
+        self.signature
     }
 
     #[inline]
@@ -294,14 +886,83 @@ impl<'a> ArchiveEntry<'a> {
         self.extra_data
     }
 
+    /// Parses this entry's [`EntryMetadata`], if its `extra_data` is large enough to hold one
+    /// (i.e. it was pushed with [`crate::builder::ArchiveBuilder::push_metadata_entry`]).
+    fn metadata(&self) -> Option<EntryMetadata> {
+        bytemuck::try_pod_read_unaligned(self.extra_data.get(..size_of::<EntryMetadata>())?).ok()
+    }
+
+    /// POSIX permission/type bits, if this entry carries [`EntryMetadata`].
+    pub fn mode(&self) -> Option<u32> {
+        self.metadata().map(|metadata| metadata.mode())
+    }
+
+    /// Last-modified time (in seconds since the Unix epoch), if this entry carries
+    /// [`EntryMetadata`].
+    pub fn mtime(&self) -> Option<u64> {
+        self.metadata().map(|metadata| metadata.mtime())
+    }
+
+    /// The target path of a `Signature::Symlink` entry, stored as the variable-length payload
+    /// following its [`EntryMetadata`].
+    pub fn symlink_target(&self) -> Option<&'a str> {
+        if self.signature != Signature::Symlink {
+            return None;
+        }
+        let target_bytes = self.extra_data.get(size_of::<EntryMetadata>()..)?;
+        if target_bytes.is_empty() {
+            return None;
+        }
+        core::str::from_utf8(target_bytes).ok()
+    }
+
+    /// The `(major, minor)` device numbers of a `Signature::CharDevice`/`Signature::BlockDevice`
+    /// entry, if this entry carries [`EntryMetadata`].
+    pub fn device_ids(&self) -> Option<(u32, u32)> {
+        self.metadata().map(|metadata| metadata.device_ids())
+    }
+
+    /// The entry's total uncompressed data length, across all of its chunks.
+    #[inline]
+    pub const fn len(&self) -> u64 {
+        self.data_len
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.data_len == 0
+    }
+
+    /// The entry's raw data bytes, when they can be borrowed directly: the archive's codec is
+    /// `Codec::Store` and the entry is a single chunk. Otherwise use
+    /// [`Archive::copy_entry_into`].
     #[inline]
     pub const fn data(&self) -> &[u8] {
         self.data
     }
+
+    /// Recomputes the CRC-32 of `self.data()` and compares it against the checksum recorded at
+    /// build time. Returns `Error::DataNotResident` when `self.data()` doesn't hold the entry's
+    /// actual bytes (any entry split into more than one content-defined chunk, or any entry under
+    /// a non-`Store` codec) — reassemble via [`Archive::copy_entry_into`] and CRC-32 that instead.
+    pub fn verify(&self) -> Result<()> {
+        if self.data.is_empty() && self.data_len != 0 {
+            return Err(Error::DataNotResident);
+        }
+
+        if crc32::crc32(self.data) == self.data_crc32 {
+            Ok(())
+        } else {
+            Err(Error::CorruptEntry)
+        }
+    }
 }
 
 pub struct ArchiveIterator<'a> {
     entries: &'a [ArchiveTableEntry],
+    chunk_table: &'a [ChunkEntry],
+    chunk_index: &'a [u32],
+    codec: Codec,
     names: &'a [u8],
     extra_data: &'a [u8],
     data: &'a [u8],
@@ -309,7 +970,7 @@ pub struct ArchiveIterator<'a> {
 }
 
 impl<'a> Iterator for ArchiveIterator<'a> {
-    type Item = ArchiveEntry<'a>;
+    type Item = Result<ArchiveEntry<'a>>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let table_entry = self.entries.get(self.index)?;
@@ -317,6 +978,9 @@ impl<'a> Iterator for ArchiveIterator<'a> {
 
         Some(ArchiveEntry::from_table_entry(
             table_entry,
+            self.codec,
+            self.chunk_table,
+            self.chunk_index,
             self.names,
             self.extra_data,
             self.data,
@@ -331,6 +995,9 @@ impl DoubleEndedIterator for ArchiveIterator<'_> {
 
         Some(ArchiveEntry::from_table_entry(
             table_entry,
+            self.codec,
+            self.chunk_table,
+            self.chunk_index,
             self.names,
             self.extra_data,
             self.data,
@@ -343,3 +1010,233 @@ impl ExactSizeIterator for ArchiveIterator<'_> {
         self.entries.len() - self.index
     }
 }
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// A tiny xorshift64 PRNG, used only to generate the arbitrary byte buffers below; not
+    /// suitable for anything security-sensitive.
+    struct Rng(u64);
+
+    impl Rng {
+        fn fill(&mut self, buf: &mut [u8]) {
+            for chunk in buf.chunks_mut(8) {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                chunk.copy_from_slice(&self.0.to_le_bytes()[..chunk.len()]);
+            }
+        }
+    }
+
+    /// Feeds `Archive::new` random byte buffers of every length up to a small bound, proving that
+    /// parsing and then fully iterating the result never panics — it only ever produces `Ok`/`Err`.
+    #[test]
+    fn parse_never_panics_on_arbitrary_bytes() {
+        let mut rng = Rng(0x1234_5678_9abc_def0);
+
+        for len in 0..512 {
+            let mut buf = alloc::vec![0u8; len];
+            rng.fill(&mut buf);
+
+            if let Ok(archive) = Archive::new(&buf) {
+                let _: Result<Vec<_>> = archive.iter().collect();
+            }
+        }
+    }
+
+    /// Truncating a well-formed archive at every possible byte boundary must never panic either,
+    /// since truncation (a partial download, a crash mid-write) is the most realistic way a real
+    /// archive ends up corrupted.
+    #[test]
+    fn parse_never_panics_on_truncated_archive() {
+        let mut builder = crate::builder::ArchiveBuilder::new();
+        builder.push_entry(Signature::File, "a", &[], b"hello, world!");
+        builder.push_entry(Signature::Directory, "b", &[], &[]);
+        let archive_bytes = builder.finish().unwrap();
+
+        for len in 0..=archive_bytes.len() {
+            if let Ok(archive) = Archive::new(&archive_bytes[..len]) {
+                let _: Result<Vec<_>> = archive.iter().collect();
+            }
+        }
+    }
+
+    /// `copy_entry_into` walks the block directory and chunk table directly, independently of
+    /// `Archive::new`'s own validation, so it needs its own panic-freedom coverage: truncating a
+    /// well-formed archive must never panic when every surviving entry is then reassembled.
+    #[test]
+    fn copy_entry_into_never_panics_on_truncated_archive() {
+        let mut builder = crate::builder::ArchiveBuilder::new();
+        builder.push_entry(Signature::File, "a", &[], b"hello, world!");
+        builder.push_entry(Signature::Directory, "b", &[], &[]);
+        let archive_bytes = builder.finish().unwrap();
+
+        for len in 0..=archive_bytes.len() {
+            let Ok(archive) = Archive::new(&archive_bytes[..len]) else {
+                continue;
+            };
+            let mut block_scratch = alloc::vec![0u8; archive.block_size().max(1) as usize];
+
+            for entry in archive.iter() {
+                let Ok(entry) = entry else { continue };
+                let Ok(data_len) = usize::try_from(entry.len()) else {
+                    continue;
+                };
+                let mut out = alloc::vec![0u8; data_len];
+                let _ = archive.copy_entry_into(&entry, &mut out, &mut block_scratch);
+            }
+        }
+    }
+
+    /// A single entry spanning several blocks round-trips through the block directory: building
+    /// with the `Store` codec and reassembling via `copy_entry_into` must reproduce the original
+    /// bytes exactly, proving the block-compression layer's offsets/lengths are wired up correctly.
+    #[test]
+    fn multi_block_entry_round_trips() {
+        let data: Vec<u8> = (0..(2 * crate::compress::BLOCK_SIZE + 1024))
+            .map(|i| (i % 251) as u8)
+            .collect();
+
+        let mut builder = crate::builder::ArchiveBuilder::new().with_codec(Codec::Store);
+        builder.push_entry(Signature::File, "big", &[], &data);
+        let archive_bytes = builder.finish().unwrap();
+
+        let archive = Archive::new(&archive_bytes).unwrap();
+        assert!(archive.block_count() >= 2);
+
+        let entry = archive.get("big").unwrap().unwrap();
+        let mut out = alloc::vec![0u8; usize::try_from(entry.len()).unwrap()];
+        let mut block_scratch = alloc::vec![0u8; archive.block_size() as usize];
+        let written = archive.copy_entry_into(&entry, &mut out, &mut block_scratch).unwrap();
+
+        assert_eq!(written, data.len());
+        assert_eq!(out, data);
+    }
+
+    /// `Archive::verify` and `ArchiveEntry::verify` must pass on an untouched archive and reject
+    /// one whose data bytes were tampered with afterwards — the entire point of the CRC32 /
+    /// whole-archive-hash integrity layer.
+    #[test]
+    fn verify_detects_corruption() {
+        let mut builder = crate::builder::ArchiveBuilder::new();
+        builder.push_entry(Signature::File, "a", &[], b"hello, world!");
+        let mut archive_bytes = builder.finish().unwrap();
+
+        {
+            let archive = Archive::new(&archive_bytes).unwrap();
+            archive.verify().unwrap();
+            let entry = archive.get("a").unwrap().unwrap();
+            entry.verify().unwrap();
+        }
+
+        let last = archive_bytes.len() - 1;
+        archive_bytes[last] ^= 0xff;
+
+        let archive = Archive::new(&archive_bytes).unwrap();
+        assert_eq!(archive.verify(), Err(Error::ChecksumMismatch));
+        let entry = archive.get("a").unwrap().unwrap();
+        assert_eq!(entry.verify(), Err(Error::CorruptEntry));
+    }
+
+    /// Pushing the same large payload under two different names must not double the chunk pool:
+    /// content-defined chunking should produce identical chunk boundaries for identical bytes, so
+    /// the second entry's chunks are all found in the dedup map instead of appended anew. The
+    /// second entry must still reassemble back to the original bytes via `copy_entry_into`.
+    #[test]
+    fn duplicate_content_is_deduplicated() {
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 256) as u8).collect();
+
+        let mut one = crate::builder::ArchiveBuilder::new();
+        one.push_entry(Signature::File, "a", &[], &data);
+        let one_bytes = one.finish().unwrap();
+        let one_archive = Archive::new(&one_bytes).unwrap();
+
+        let mut two = crate::builder::ArchiveBuilder::new();
+        two.push_entry(Signature::File, "a", &[], &data);
+        two.push_entry(Signature::File, "b", &[], &data);
+        let two_bytes = two.finish().unwrap();
+        let two_archive = Archive::new(&two_bytes).unwrap();
+
+        assert_eq!(one_archive.chunk_count(), two_archive.chunk_count());
+
+        let entry_b = two_archive.get("b").unwrap().unwrap();
+        let mut out = alloc::vec![0u8; usize::try_from(entry_b.len()).unwrap()];
+        let mut block_scratch = alloc::vec![0u8; two_archive.block_size() as usize];
+        two_archive
+            .copy_entry_into(&entry_b, &mut out, &mut block_scratch)
+            .unwrap();
+        assert_eq!(out, data);
+    }
+
+    /// `push_metadata_entry`'s mode/uid/gid/mtime and symlink target must survive a round trip,
+    /// and the entry's `Signature` must come back as `Symlink` rather than the default `File`.
+    #[test]
+    fn metadata_entry_round_trips() {
+        let metadata = EntryMetadata::new(0o120_777, 1000, 1000, 1_700_000_000, 0, 0);
+
+        let mut builder = crate::builder::ArchiveBuilder::new();
+        builder.push_metadata_entry(Signature::Symlink, "link", metadata, Some("target.txt"), &[]);
+        let archive_bytes = builder.finish().unwrap();
+
+        let archive = Archive::new(&archive_bytes).unwrap();
+        let entry = archive.get("link").unwrap().unwrap();
+
+        assert_eq!(entry.signature(), Signature::Symlink);
+        assert_eq!(entry.mode(), Some(0o120_777));
+        assert_eq!(entry.mtime(), Some(1_700_000_000));
+        assert_eq!(entry.symlink_target(), Some("target.txt"));
+        assert_eq!(entry.device_ids(), Some((0, 0)));
+    }
+
+    /// `Archive::get` must binary-search the sorted name index to the right entry regardless of
+    /// push order, and return `Ok(None)` for a missing name.
+    #[test]
+    fn get_looks_up_by_name() {
+        let mut builder = crate::builder::ArchiveBuilder::new();
+        builder.push_entry(Signature::File, "zebra", &[], b"z");
+        builder.push_entry(Signature::File, "apple", &[], b"a");
+        builder.push_entry(Signature::File, "mango", &[], b"m");
+        let archive_bytes = builder.finish().unwrap();
+        let archive = Archive::new(&archive_bytes).unwrap();
+
+        assert_eq!(archive.get("apple").unwrap().unwrap().data(), b"a");
+        assert_eq!(archive.get("mango").unwrap().unwrap().data(), b"m");
+        assert_eq!(archive.get("zebra").unwrap().unwrap().data(), b"z");
+        assert!(archive.get("missing").unwrap().is_none());
+    }
+
+    /// `finish` must reject duplicate names outright rather than let `get` return an arbitrary
+    /// one of them.
+    #[test]
+    fn finish_rejects_duplicate_names() {
+        let mut builder = crate::builder::ArchiveBuilder::new();
+        builder.push_entry(Signature::File, "dup", &[], b"one");
+        builder.push_entry(Signature::File, "dup", &[], b"two");
+
+        assert_eq!(builder.finish(), Err(Error::DuplicateName));
+    }
+
+    /// Splitting an archive into parts small enough to force several of them, then reassembling
+    /// via `Archive::from_parts`, must reproduce every entry's original data exactly.
+    #[test]
+    fn split_archive_round_trips() {
+        let mut builder = crate::builder::ArchiveBuilder::new();
+        builder.push_entry(Signature::File, "a", &[], b"hello, world!");
+        builder.push_entry(Signature::File, "b", &[], b"a second, distinct file's contents");
+        let parts = builder.finish_split(256).unwrap();
+        assert!(parts.len() > 1);
+
+        let part_refs: Vec<&[u8]> = parts.iter().map(Vec::as_slice).collect();
+        let mut buffer = Vec::new();
+        let archive = Archive::from_parts(&part_refs, &mut buffer).unwrap();
+
+        assert_eq!(archive.get("a").unwrap().unwrap().data(), b"hello, world!");
+        assert_eq!(
+            archive.get("b").unwrap().unwrap().data(),
+            b"a second, distinct file's contents"
+        );
+    }
+}
@@ -23,12 +23,19 @@ pub fn main() -> Result<(), xshell::Error> {
                 archive_builder.push_entry(oaf::Signature::File, &file_name, &[], &file);
             }
 
-            let archive = archive_builder.finish();
+            let archive_bytes = archive_builder.finish().unwrap();
 
-            let archive = oaf::Archive::new(&archive).unwrap();
+            let archive = oaf::Archive::new(&archive_bytes).unwrap();
+            let mut block_scratch = vec![0u8; archive.block_size() as usize];
 
             for entry in archive.iter() {
-                let data_str = std::str::from_utf8(entry.data()).unwrap();
+                let entry = entry.unwrap();
+
+                let mut data = vec![0u8; usize::try_from(entry.len()).unwrap()];
+                archive
+                    .copy_entry_into(&entry, &mut data, &mut block_scratch)
+                    .unwrap();
+                let data_str = std::str::from_utf8(&data).unwrap();
 
                 println!("\n{}\n{}", entry.name(), data_str);
             }